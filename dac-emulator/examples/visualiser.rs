@@ -2,40 +2,67 @@
 //!
 //! In this example we:
 //!
-//! 1. Create the default DAC emulator.
-//! 2. Spawn the broadcaster on its own thread so that it sends UDP broadcasts once per second.
-//! 3. Spawn the listener on its own thread so that it may listen for stream connection requests.
-//! 4. Loop at 60 FPS (nannou's default app loop).
-//! 5. On each loop, check whether or not a new stream has been established.
-//! 6. If we have a stream, check for the latest frame points.
-//! 7. In our `view` function, draw the laser frame to the bounds of the window.
+//! 1. Bind a `listener::Listener` to accept incoming stream connections.
+//! 2. Spawn the listener on its own thread so that it may listen for stream connection requests.
+//! 3. Loop at 60 FPS (nannou's default app loop).
+//! 4. On each loop, check whether or not a new stream has been established.
+//! 5. If we have a stream, check for the latest frame points.
+//! 6. In our `view` function, draw the laser frame to the bounds of the window.
+//!
+//! Pass `--record <path>` to additionally capture every received frame to disk, or
+//! `--replay <path>` to skip the live connection entirely and play a capture back instead.
 
 extern crate ether_dream_dac_emulator;
 extern crate nannou;
 
-use ether_dream_dac_emulator::{ether_dream, broadcaster, listener};
+use ether_dream_dac_emulator::{ether_dream, listener, optimize, player, recorder, status, transform};
 use nannou::prelude::*;
 use std::sync::mpsc;
+use std::time::Instant;
 use std::{net, thread};
 
+/// The default Ether Dream TCP listen port.
+const DEFAULT_PORT: u16 = 7765;
+
 fn main() {
     nannou::run(model, event, view);
 }
 
+/// What to read frames from, selected via the `--record`/`--replay` command-line flags.
+enum Mode {
+    Live,
+    Record(String),
+    Replay(String),
+}
+
+fn parse_mode() -> Mode {
+    let mut args = std::env::args().skip(1);
+    match (args.next(), args.next()) {
+        (Some(ref flag), Some(path)) if flag == "--record" => Mode::Record(path),
+        (Some(ref flag), Some(path)) if flag == "--replay" => Mode::Replay(path),
+        _ => Mode::Live,
+    }
+}
+
 struct Model {
-    broadcaster: broadcaster::Handle,
     stream: Option<listener::ActiveStream>,
     frame_points: Vec<ether_dream::protocol::DacPoint>,
     stream_rx: mpsc::Receiver<(listener::ActiveStream, net::SocketAddr)>,
+    // Transforms applied to each received frame before it is drawn.
+    transformers: Vec<transform::Transformer>,
+    // When set, frames are run through the path optimizer before being drawn.
+    optimizer: Option<optimize::Config>,
+    // The current stream's simulated DAC status, displayed as a buffer-fullness bar.
+    status: Option<status::Status>,
+    // When set (`--replay`), frames are pulled from here instead of from `stream`.
+    replay: Option<player::Player>,
+    // When set (`--record`), every frame received from `stream` is appended here.
+    recorder: Option<(recorder::Recorder<std::fs::File>, Option<Instant>)>,
 }
 
 fn model(_app: &App) -> Model {
-    let dac_description = Default::default();
-    let (broadcaster, mut listener) = ether_dream_dac_emulator::new(dac_description).unwrap();
-
-    // Run the DAC broadcaster.
-    let broadcaster = broadcaster.spawn().unwrap();
-    broadcaster.spawn_once_per_second_timer().unwrap();
+    let addr = net::SocketAddr::from(([0, 0, 0, 0], DEFAULT_PORT));
+    let mut listener = listener::Listener::bind(addr).unwrap();
 
     // Spawn a thread for the listener.
     let (stream_tx, stream_rx) = mpsc::channel();
@@ -53,11 +80,60 @@ fn model(_app: &App) -> Model {
     // The buffer to use for collecting frame points.
     let frame_points = Vec::new();
 
-    Model { broadcaster, stream, stream_rx, frame_points }
+    // No transforms applied by default; push `transform::Transformer`s here to test clients
+    // against translated, replicated or dimmed geometry.
+    let transformers = Vec::new();
+
+    // Optimization is off by default; set this to an `optimize::Config` to see the frame
+    // reordered, slewed and resampled the way a real DAC-driving client would send it.
+    let optimizer = None;
+
+    // No stream connected yet, so there's no status to report.
+    let status = None;
+
+    let (replay, recorder) = match parse_mode() {
+        Mode::Live => (None, None),
+        Mode::Record(path) => (None, Some((recorder::Recorder::create(path).unwrap(), None))),
+        Mode::Replay(path) => {
+            let frames = player::read_recording(path).unwrap();
+            (Some(player::Player::new(frames, 1.0)), None)
+        }
+    };
+
+    Model {
+        stream,
+        stream_rx,
+        frame_points,
+        transformers,
+        optimizer,
+        status,
+        replay,
+        recorder,
+    }
 }
 
 fn event(_app: &App, mut model: Model, event: Event) -> Model {
     if let Event::Update(_update) = event {
+        // In `--replay` mode, pull frames from the recording instead of a live connection.
+        if let Some(player) = model.replay.as_mut() {
+            match player.try_next_frame() {
+                Ok(Some(points)) => {
+                    let points = transform::apply_all(&model.transformers, &points);
+                    let points = match &model.optimizer {
+                        Some(config) => optimize::optimize(&points, config),
+                        None => points,
+                    };
+                    model.frame_points = points;
+                }
+                Ok(None) => (),
+                Err(_) => {
+                    println!("Recording finished.");
+                    model.replay = None;
+                }
+            }
+            return model;
+        }
+
         // Check for stream connections.
         if let Ok((stream, addr)) = model.stream_rx.try_recv() {
             println!("Connected to {}!", addr);
@@ -80,10 +156,34 @@ fn event(_app: &App, mut model: Model, event: Event) -> Model {
                 }
             }
             if let Some(frame) = latest_frame {
-                model.frame_points.clear();
-                model.frame_points.extend(frame.iter().cloned());
+                let points: Vec<_> = frame.iter().cloned().collect();
+
+                // In `--record` mode, capture the frame exactly as received, before any
+                // display-only transforms or optimization are applied to it.
+                if let Some((rec, last_at)) = model.recorder.as_mut() {
+                    let now = Instant::now();
+                    let elapsed_since_prev = last_at.map(|at| now.duration_since(at)).unwrap_or_default();
+                    *last_at = Some(now);
+                    let point_rate = model.status.map(|s| s.point_rate).unwrap_or(0);
+                    let recorded = recorder::RecordedFrame {
+                        points: points.clone(),
+                        point_rate,
+                        elapsed_since_prev,
+                    };
+                    rec.record_frame(&recorded).unwrap();
+                }
+
+                let points = transform::apply_all(&model.transformers, &points);
+                let points = match &model.optimizer {
+                    Some(config) => optimize::optimize(&points, config),
+                    None => points,
+                };
+                model.frame_points = points;
             }
         }
+
+        // Keep the displayed status in sync with the stream's simulated DAC state.
+        model.status = model.stream.as_ref().map(|stream| stream.status());
     }
     model
 }
@@ -118,6 +218,16 @@ fn view(app: &App, model: &Model, frame: Frame) -> Frame {
             .rgb(ar, ag, ab);
     }
 
+    // Draw the simulated buffer fullness as a bar along the bottom of the window.
+    if let Some(status) = model.status {
+        let frac = status.buffer_fullness as f32 / status.capacity.max(1) as f32;
+        let bar_w = win_rect.w() * frac;
+        draw.rect()
+            .x_y(win_rect.left() + bar_w * 0.5, win_rect.bottom() + 10.0)
+            .w_h(bar_w, 8.0)
+            .color(WHITE);
+    }
+
     draw.to_frame(app, &frame).unwrap();
 
     // Return the cleared frame.
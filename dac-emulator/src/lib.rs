@@ -0,0 +1,11 @@
+//! An emulator of the Ether Dream DAC, useful for developing and testing software that talks to
+//! real Ether Dream hardware without one on hand.
+
+pub extern crate ether_dream;
+
+pub mod listener;
+pub mod optimize;
+pub mod player;
+pub mod recorder;
+pub mod status;
+pub mod transform;
@@ -0,0 +1,172 @@
+//! Accepts incoming stream connections and dispatches the commands a connected client sends,
+//! driving a `status::Engine` per stream so responses match real hardware behaviour.
+
+use crate::status::{AckResponse, Engine, Status};
+use ether_dream::protocol::DacPoint;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+/// The default number of points the simulated DAC's point buffer can hold.
+const DEFAULT_BUFFER_CAPACITY: u16 = 1800;
+
+/// A command received from a connected client.
+#[derive(Debug, Clone)]
+enum Command {
+    PrepareStream,
+    Begin { point_rate: u32 },
+    Stop,
+    WritePoints(Vec<DacPoint>),
+}
+
+/// Listens for incoming stream connections.
+pub struct Listener {
+    tcp: TcpListener,
+}
+
+impl Listener {
+    /// Bind a listener to the given address.
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        Ok(Listener { tcp: TcpListener::bind(addr)? })
+    }
+
+    /// Block until a client connects, spawning a thread to read and dispatch its commands and
+    /// returning the `ActiveStream` used to read frames and query status.
+    pub fn accept(&mut self) -> io::Result<(ActiveStream, SocketAddr)> {
+        let (tcp_stream, addr) = self.tcp.accept()?;
+        let engine = Arc::new(Mutex::new(Engine::new(DEFAULT_BUFFER_CAPACITY)));
+        let (frame_tx, frame_rx) = mpsc::channel();
+        let reader_engine = engine.clone();
+        thread::spawn(move || {
+            let _ = read_commands(tcp_stream, reader_engine, frame_tx);
+        });
+        Ok((ActiveStream { engine, frame_rx }, addr))
+    }
+}
+
+/// A stream accepted from a connected client, exposing the frames it has sent and its
+/// simulated DAC `Status`.
+pub struct ActiveStream {
+    engine: Arc<Mutex<Engine>>,
+    frame_rx: mpsc::Receiver<Vec<DacPoint>>,
+}
+
+impl ActiveStream {
+    /// The handle used to read frames sent by this client, e.g. by the nannou visualiser
+    /// example.
+    pub fn output(&self) -> Output<'_> {
+        Output { frame_rx: &self.frame_rx }
+    }
+
+    /// A snapshot of this stream's simulated DAC state: playback state, buffer fullness, and
+    /// the response to the last command processed.
+    pub fn status(&self) -> Status {
+        self.engine.lock().unwrap().status()
+    }
+}
+
+/// Yields the frames received on an `ActiveStream`.
+pub struct Output<'a> {
+    frame_rx: &'a mpsc::Receiver<Vec<DacPoint>>,
+}
+
+impl<'a> Output<'a> {
+    /// The oldest received frame not yet returned by a previous call, if any, or an error if the
+    /// stream has shut down.
+    pub fn try_next_frame(&self) -> Result<Option<Vec<DacPoint>>, mpsc::TryRecvError> {
+        match self.frame_rx.try_recv() {
+            Ok(frame) => Ok(Some(frame)),
+            Err(mpsc::TryRecvError::Empty) => Ok(None),
+            Err(err @ mpsc::TryRecvError::Disconnected) => Err(err),
+        }
+    }
+}
+
+/// Reads commands from `stream` until it closes, driving `engine` and forwarding the points
+/// from any accepted write onto `frame_tx` so `ActiveStream::output` can pick them up.
+fn read_commands(
+    mut stream: TcpStream,
+    engine: Arc<Mutex<Engine>>,
+    frame_tx: mpsc::Sender<Vec<DacPoint>>,
+) -> io::Result<()> {
+    let mut last_command_at = Instant::now();
+    loop {
+        let command = match read_command(&mut stream) {
+            Ok(command) => command,
+            Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        // Drain the point buffer for the time spent waiting on this command, so
+        // `buffer_fullness` reflects a DAC that's been consuming points at `point_rate` the
+        // whole time, not just a one-way counter that only ever fills up.
+        let now = Instant::now();
+        engine.lock().unwrap().drain(now.duration_since(last_command_at));
+        last_command_at = now;
+
+        let response = match command {
+            Command::PrepareStream => engine.lock().unwrap().prepare_stream(),
+            Command::Begin { point_rate } => engine.lock().unwrap().begin(point_rate),
+            Command::Stop => engine.lock().unwrap().stop(),
+            Command::WritePoints(points) => {
+                let response = engine.lock().unwrap().write_points(points.len() as u16);
+                if response == AckResponse::Ack && frame_tx.send(points).is_err() {
+                    return Ok(());
+                }
+                response
+            }
+        };
+        write_response(&mut stream, response)?;
+    }
+}
+
+/// Parse a single command off the wire: a one-byte tag (`p`repare_stream, `b`egin, `s`top,
+/// `d`ata), followed by that command's payload.
+fn read_command(stream: &mut TcpStream) -> io::Result<Command> {
+    let mut tag = [0u8; 1];
+    stream.read_exact(&mut tag)?;
+    match tag[0] {
+        b'p' => Ok(Command::PrepareStream),
+        b'b' => {
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf)?;
+            Ok(Command::Begin { point_rate: u32::from_le_bytes(buf) })
+        }
+        b's' => Ok(Command::Stop),
+        b'd' => {
+            let mut count_buf = [0u8; 4];
+            stream.read_exact(&mut count_buf)?;
+            let count = u32::from_le_bytes(count_buf);
+            let mut points = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let mut point_buf = [0u8; 10];
+                stream.read_exact(&mut point_buf)?;
+                points.push(DacPoint {
+                    x: i16::from_le_bytes([point_buf[0], point_buf[1]]),
+                    y: i16::from_le_bytes([point_buf[2], point_buf[3]]),
+                    r: u16::from_le_bytes([point_buf[4], point_buf[5]]),
+                    g: u16::from_le_bytes([point_buf[6], point_buf[7]]),
+                    b: u16::from_le_bytes([point_buf[8], point_buf[9]]),
+                    ..Default::default()
+                });
+            }
+            Ok(Command::WritePoints(points))
+        }
+        other => {
+            let msg = format!("unrecognised command byte: {}", other);
+            Err(io::Error::new(io::ErrorKind::InvalidData, msg))
+        }
+    }
+}
+
+fn write_response(stream: &mut TcpStream, response: AckResponse) -> io::Result<()> {
+    let byte = match response {
+        AckResponse::Ack => b'a',
+        AckResponse::Full => b'F',
+        AckResponse::Invalid => b'I',
+    };
+    stream.write_all(&[byte])
+}
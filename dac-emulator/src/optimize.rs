@@ -0,0 +1,284 @@
+//! An optional laser path optimization stage, similar in spirit to `lasy`, that resamples a
+//! frame of `DacPoint`s to a target point budget: lit segments are reordered to reduce blank
+//! travel, blanked points are inserted between them, and dwell points are added at sharp corners
+//! before the resample pass distributes points evenly by arc length. That pass spaces points by
+//! arc length alone, so duplicated dwell points (which add no length) do not survive into its
+//! output, and the number of blanked points along a jump follows the jump's length rather than
+//! `Config::blank_travel_points`.
+
+use ether_dream::protocol::DacPoint;
+
+/// Configuration for the path optimizer.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    /// The exact number of points the optimized frame should contain. `0` skips resampling and
+    /// returns the joined path with its own point count.
+    pub points_per_frame: u32,
+    /// The number of blanked points interpolated between the end of one segment and the start
+    /// of the next, before resampling.
+    pub blank_travel_points: u32,
+    /// Interior corner angles (in radians) below this threshold receive extra dwell points.
+    pub corner_dwell_angle: f32,
+    /// The number of duplicate points inserted at a detected sharp corner, before resampling.
+    pub corner_dwell_points: u32,
+}
+
+/// Optimize a frame of points for the given configuration.
+pub fn optimize(points: &[DacPoint], config: &Config) -> Vec<DacPoint> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let segments = split_into_segments(points);
+    if segments.is_empty() {
+        return Vec::new();
+    }
+    let ordered = order_segments(segments);
+    let with_dwells: Vec<Vec<DacPoint>> = ordered
+        .iter()
+        .map(|segment| insert_corner_dwells(segment, config))
+        .collect();
+    let joined = join_with_blanks(&with_dwells, config.blank_travel_points);
+    resample(&joined, config.points_per_frame)
+}
+
+fn is_blank(p: &DacPoint) -> bool {
+    p.r == 0 && p.g == 0 && p.b == 0
+}
+
+/// Split a frame into contiguous lit segments (including lone bright dots), discarding the
+/// blanked points that separate them.
+fn split_into_segments(points: &[DacPoint]) -> Vec<Vec<DacPoint>> {
+    let mut segments = Vec::new();
+    let mut current = Vec::new();
+    for p in points {
+        if is_blank(p) {
+            if !current.is_empty() {
+                segments.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(*p);
+        }
+    }
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+/// Greedily reorder segments (reversing them where that's shorter) to approximate the
+/// minimal-blank-travel Eulerian-style traversal. Finding the true optimum is a travelling-
+/// salesman-style problem, so we settle for nearest-neighbour here.
+fn order_segments(mut segments: Vec<Vec<DacPoint>>) -> Vec<Vec<DacPoint>> {
+    let mut ordered = Vec::with_capacity(segments.len());
+    let first = segments.remove(0);
+    let mut current_end = *first.last().unwrap();
+    ordered.push(first);
+    while !segments.is_empty() {
+        let (idx, reversed) = segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| {
+                let d_start = dist(&current_end, segment.first().unwrap());
+                let d_end = dist(&current_end, segment.last().unwrap());
+                if d_start <= d_end { (i, false, d_start) } else { (i, true, d_end) }
+            })
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+            .map(|(i, reversed, _)| (i, reversed))
+            .unwrap();
+        let mut next = segments.remove(idx);
+        if reversed {
+            next.reverse();
+        }
+        current_end = *next.last().unwrap();
+        ordered.push(next);
+    }
+    ordered
+}
+
+/// Duplicate points at interior corners sharper than `config.corner_dwell_angle` so the beam
+/// dwells there rather than rounding the corner off.
+fn insert_corner_dwells(segment: &[DacPoint], config: &Config) -> Vec<DacPoint> {
+    if segment.len() < 3 || config.corner_dwell_points == 0 {
+        return segment.to_vec();
+    }
+    let mut out = Vec::with_capacity(segment.len());
+    out.push(segment[0]);
+    for window in segment.windows(3) {
+        let (a, b, c) = (&window[0], &window[1], &window[2]);
+        out.push(*b);
+        if interior_angle(a, b, c) < config.corner_dwell_angle {
+            for _ in 0..config.corner_dwell_points {
+                out.push(*b);
+            }
+        }
+    }
+    out.push(segment[segment.len() - 1]);
+    out
+}
+
+fn interior_angle(a: &DacPoint, b: &DacPoint, c: &DacPoint) -> f32 {
+    let v1 = ((a.x - b.x) as f32, (a.y - b.y) as f32);
+    let v2 = ((c.x - b.x) as f32, (c.y - b.y) as f32);
+    let mag = (v1.0 * v1.0 + v1.1 * v1.1).sqrt() * (v2.0 * v2.0 + v2.1 * v2.1).sqrt();
+    if mag == 0.0 {
+        return std::f32::consts::PI;
+    }
+    let cos_angle = (v1.0 * v2.0 + v1.1 * v2.1) / mag;
+    cos_angle.clamp(-1.0, 1.0).acos()
+}
+
+/// Join ordered segments into a single path, inserting a short linear-interpolated run of
+/// blanked points between each pair so the galvos have time to slew across the jump.
+fn join_with_blanks(segments: &[Vec<DacPoint>], blank_travel_points: u32) -> Vec<DacPoint> {
+    let mut out: Vec<DacPoint> = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        if i > 0 {
+            let prev_end = *out.last().unwrap();
+            let next_start = *segment.first().unwrap();
+            for step in 1..=blank_travel_points {
+                let t = step as f32 / (blank_travel_points + 1) as f32;
+                out.push(lerp_blank(&prev_end, &next_start, t));
+            }
+        }
+        out.extend(segment.iter().cloned());
+    }
+    out
+}
+
+fn lerp_blank(a: &DacPoint, b: &DacPoint, t: f32) -> DacPoint {
+    DacPoint {
+        x: lerp_i16(a.x, b.x, t),
+        y: lerp_i16(a.y, b.y, t),
+        r: 0,
+        g: 0,
+        b: 0,
+        ..*a
+    }
+}
+
+/// Resample the whole path to exactly `target` points, distributing them proportionally to
+/// segment arc length. A `target` of `0` returns the path unchanged.
+fn resample(points: &[DacPoint], target: u32) -> Vec<DacPoint> {
+    if target == 0 {
+        return points.to_vec();
+    }
+    // A lone point (or multiple coincident points) has no arc length to distribute along, but
+    // still owes the caller exactly `target` points: repeat it rather than passing it through.
+    if points.len() < 2 {
+        return match points.first() {
+            Some(p) => vec![*p; target as usize],
+            None => Vec::new(),
+        };
+    }
+    let mut cumulative = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    cumulative.push(0.0);
+    for window in points.windows(2) {
+        total += dist(&window[0], &window[1]);
+        cumulative.push(total);
+    }
+    if total == 0.0 {
+        return vec![points[0]; target as usize];
+    }
+    let mut out = Vec::with_capacity(target as usize);
+    let steps = target.saturating_sub(1).max(1);
+    for i in 0..target {
+        let target_len = total * i as f32 / steps as f32;
+        let idx = cumulative
+            .iter()
+            .position(|&len| len >= target_len)
+            .unwrap_or(cumulative.len() - 1)
+            .max(1);
+        let seg_len = cumulative[idx] - cumulative[idx - 1];
+        let t = if seg_len > 0.0 { (target_len - cumulative[idx - 1]) / seg_len } else { 0.0 };
+        out.push(lerp_point(&points[idx - 1], &points[idx], t));
+    }
+    out
+}
+
+fn lerp_point(a: &DacPoint, b: &DacPoint, t: f32) -> DacPoint {
+    DacPoint {
+        x: lerp_i16(a.x, b.x, t),
+        y: lerp_i16(a.y, b.y, t),
+        r: lerp_u16(a.r, b.r, t),
+        g: lerp_u16(a.g, b.g, t),
+        b: lerp_u16(a.b, b.b, t),
+        ..*a
+    }
+}
+
+fn lerp_i16(a: i16, b: i16, t: f32) -> i16 {
+    (a as f32 + (b - a) as f32 * t).round() as i16
+}
+
+fn lerp_u16(a: u16, b: u16, t: f32) -> u16 {
+    (a as f32 + (b as f32 - a as f32) * t).round() as u16
+}
+
+fn dist(a: &DacPoint, b: &DacPoint) -> f32 {
+    let dx = (a.x - b.x) as f32;
+    let dy = (a.y - b.y) as f32;
+    (dx * dx + dy * dy).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(x: i16, y: i16) -> DacPoint {
+        DacPoint { x, y, r: 0xffff, g: 0xffff, b: 0xffff, ..Default::default() }
+    }
+
+    fn blank(x: i16, y: i16) -> DacPoint {
+        DacPoint { x, y, r: 0, g: 0, b: 0, ..Default::default() }
+    }
+
+    #[test]
+    fn split_into_segments_keeps_lone_bright_dots() {
+        let points = vec![blank(0, 0), lit(5, 5), blank(10, 10), lit(1, 1), lit(2, 2)];
+        let segments = split_into_segments(&points);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0], vec![lit(5, 5)]);
+        assert_eq!(segments[1], vec![lit(1, 1), lit(2, 2)]);
+    }
+
+    #[test]
+    fn resample_produces_exact_target_count() {
+        let points = vec![lit(0, 0), lit(100, 0), lit(100, 100)];
+        let out = resample(&points, 10);
+        assert_eq!(out.len(), 10);
+    }
+
+    #[test]
+    fn optimize_repeats_lone_dot_to_target_count() {
+        let points = vec![blank(0, 0), lit(5, 5), blank(10, 10)];
+        let config = Config {
+            points_per_frame: 500,
+            blank_travel_points: 3,
+            corner_dwell_angle: 0.5,
+            corner_dwell_points: 2,
+        };
+        let out = optimize(&points, &config);
+        assert_eq!(out.len(), 500);
+        assert!(out.iter().all(|p| *p == lit(5, 5)));
+    }
+
+    #[test]
+    fn optimize_respects_points_per_frame() {
+        let points = vec![
+            lit(0, 0),
+            lit(100, 0),
+            blank(100, 0),
+            lit(0, 100),
+            lit(100, 100),
+        ];
+        let config = Config {
+            points_per_frame: 20,
+            blank_travel_points: 3,
+            corner_dwell_angle: 0.5,
+            corner_dwell_points: 2,
+        };
+        let out = optimize(&points, &config);
+        assert_eq!(out.len(), 20);
+    }
+}
@@ -0,0 +1,102 @@
+//! Replays recordings produced by the `recorder` module. `Player::try_next_frame` mirrors
+//! `listener::Output::try_next_frame`'s shape so the nannou visualiser example can drive
+//! itself from a recording in place of a live connection with no change to its draw path.
+
+use crate::recorder::RecordedFrame;
+use ether_dream::protocol::DacPoint;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Read an entire recording produced by `recorder::Recorder` into memory.
+pub fn read_recording<P: AsRef<Path>>(path: P) -> io::Result<Vec<RecordedFrame>> {
+    let mut file = fs::File::open(path)?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+
+    let mut frames = Vec::new();
+    let mut cursor = &buf[..];
+    while !cursor.is_empty() {
+        let point_count = read_u32(&mut cursor)?;
+        let point_rate = read_u32(&mut cursor)?;
+        let elapsed_since_prev = Duration::from_nanos(read_u64(&mut cursor)?);
+        let mut points = Vec::with_capacity(point_count as usize);
+        for _ in 0..point_count {
+            let x = read_i16(&mut cursor)?;
+            let y = read_i16(&mut cursor)?;
+            let r = read_u16(&mut cursor)?;
+            let g = read_u16(&mut cursor)?;
+            let b = read_u16(&mut cursor)?;
+            points.push(DacPoint { x, y, r, g, b, ..Default::default() });
+        }
+        frames.push(RecordedFrame { points, point_rate, elapsed_since_prev });
+    }
+    Ok(frames)
+}
+
+/// Replays a loaded recording at either the originally recorded frame rate or a user-specified
+/// one, polled the same way a live `listener::Output` is polled.
+pub struct Player {
+    frames: std::vec::IntoIter<RecordedFrame>,
+    rate_multiplier: f64,
+    // The next frame, peeked off `frames` and scheduled as soon as the previous one was
+    // released, so its own `elapsed_since_prev` gates *its* release rather than the one after.
+    pending: Option<(RecordedFrame, Instant)>,
+}
+
+impl Player {
+    /// Create a player over the given frames. `rate_multiplier` of `1.0` plays back at the
+    /// originally recorded rate, `2.0` at double speed, and so on.
+    pub fn new(frames: Vec<RecordedFrame>, rate_multiplier: f64) -> Self {
+        Player { frames: frames.into_iter(), rate_multiplier, pending: None }
+    }
+
+    /// The next frame's points, once its scheduled playback time has elapsed, mirroring
+    /// `listener::Output::try_next_frame`'s `Ok(None)` "nothing yet" result. Returns an error
+    /// once the recording is exhausted, mirroring a live stream shutting down.
+    pub fn try_next_frame(&mut self) -> io::Result<Option<Vec<DacPoint>>> {
+        if self.pending.is_none() {
+            let frame = self.frames.next().ok_or_else(|| io::Error::other("recording finished"))?;
+            let delay = frame.elapsed_since_prev.mul_f64(1.0 / self.rate_multiplier);
+            self.pending = Some((frame, Instant::now() + delay));
+        }
+        let (_, at) = self.pending.as_ref().unwrap();
+        if Instant::now() < *at {
+            return Ok(None);
+        }
+        let (frame, _) = self.pending.take().unwrap();
+        Ok(Some(frame.points))
+    }
+}
+
+fn read_u32(cursor: &mut &[u8]) -> io::Result<u32> {
+    let bytes = read_bytes(cursor, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u64(cursor: &mut &[u8]) -> io::Result<u64> {
+    let bytes = read_bytes(cursor, 8)?;
+    let mut arr = [0u8; 8];
+    arr.copy_from_slice(bytes);
+    Ok(u64::from_le_bytes(arr))
+}
+
+fn read_i16(cursor: &mut &[u8]) -> io::Result<i16> {
+    let bytes = read_bytes(cursor, 2)?;
+    Ok(i16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u16(cursor: &mut &[u8]) -> io::Result<u16> {
+    let bytes = read_bytes(cursor, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_bytes<'a>(cursor: &mut &'a [u8], n: usize) -> io::Result<&'a [u8]> {
+    if cursor.len() < n {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated recording"));
+    }
+    let (head, tail) = cursor.split_at(n);
+    *cursor = tail;
+    Ok(head)
+}
@@ -0,0 +1,195 @@
+//! A model of the DAC's playback engine: its state machine, point buffer, and the responses it
+//! returns for each command. `listener` drives an `Engine` per connected stream and exposes its
+//! current `Status` as `listener::ActiveStream::status`.
+
+use std::time::Duration;
+
+/// The state of the simulated DAC's playback engine.
+///
+/// A stream begins `Idle`, moves to `Prepare` once a `prepare_stream` command is acknowledged,
+/// and only enters `Playing` once a `begin` command is accepted from the `Prepare` state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackState {
+    Idle,
+    Prepare,
+    Playing,
+}
+
+/// The response returned for the most recently processed command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckResponse {
+    /// The command was accepted.
+    Ack,
+    /// The command was rejected because it arrived in the wrong playback state.
+    Invalid,
+    /// A point-buffer write was rejected because it would overfill the buffer.
+    Full,
+}
+
+/// A snapshot of a stream's simulated DAC state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub playback_state: PlaybackState,
+    pub buffer_fullness: u16,
+    pub capacity: u16,
+    pub point_rate: u32,
+    pub last_ack: AckResponse,
+}
+
+/// Simulates the DAC playback engine behind a single stream: its `PlaybackState`, point buffer
+/// fullness, and the responses the emulator should send back to the client for each command.
+#[derive(Debug, Clone)]
+pub struct Engine {
+    state: PlaybackState,
+    capacity: u16,
+    fullness: u16,
+    point_rate: u32,
+    last_ack: AckResponse,
+}
+
+impl Engine {
+    /// Create a new engine with the given point buffer `capacity`.
+    pub fn new(capacity: u16) -> Self {
+        Engine {
+            state: PlaybackState::Idle,
+            capacity,
+            fullness: 0,
+            point_rate: 0,
+            last_ack: AckResponse::Ack,
+        }
+    }
+
+    /// A snapshot of the engine's current status.
+    pub fn status(&self) -> Status {
+        Status {
+            playback_state: self.state,
+            buffer_fullness: self.fullness,
+            capacity: self.capacity,
+            point_rate: self.point_rate,
+            last_ack: self.last_ack,
+        }
+    }
+
+    /// Handle a `prepare_stream` command, valid only from `Idle`.
+    pub fn prepare_stream(&mut self) -> AckResponse {
+        self.last_ack = match self.state {
+            PlaybackState::Idle => {
+                self.state = PlaybackState::Prepare;
+                self.fullness = 0;
+                AckResponse::Ack
+            }
+            _ => AckResponse::Invalid,
+        };
+        self.last_ack
+    }
+
+    /// Handle a `begin` command, valid only from `Prepare`.
+    pub fn begin(&mut self, point_rate: u32) -> AckResponse {
+        self.last_ack = match self.state {
+            PlaybackState::Prepare => {
+                self.state = PlaybackState::Playing;
+                self.point_rate = point_rate;
+                AckResponse::Ack
+            }
+            _ => AckResponse::Invalid,
+        };
+        self.last_ack
+    }
+
+    /// Handle a `stop` command, returning the engine to `Idle` from any state.
+    pub fn stop(&mut self) -> AckResponse {
+        self.state = PlaybackState::Idle;
+        self.fullness = 0;
+        self.point_rate = 0;
+        self.last_ack = AckResponse::Ack;
+        self.last_ack
+    }
+
+    /// Handle the client writing `n` new points into the point buffer, returning `Full` rather
+    /// than `Ack` if doing so would overfill `capacity`.
+    pub fn write_points(&mut self, n: u16) -> AckResponse {
+        self.last_ack = if self.fullness.saturating_add(n) > self.capacity {
+            AckResponse::Full
+        } else {
+            self.fullness += n;
+            AckResponse::Ack
+        };
+        self.last_ack
+    }
+
+    /// Drain the point buffer at the configured `point_rate`, simulating playback over the
+    /// given `elapsed` duration. A no-op unless the engine is `Playing`.
+    pub fn drain(&mut self, elapsed: Duration) {
+        if self.state != PlaybackState::Playing {
+            return;
+        }
+        let drained = (self.point_rate as f64 * elapsed.as_secs_f64()).round() as u16;
+        self.fullness = self.fullness.saturating_sub(drained);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn begin_before_prepare_is_invalid() {
+        let mut engine = Engine::new(100);
+        assert_eq!(engine.begin(1000), AckResponse::Invalid);
+        assert_eq!(engine.status().playback_state, PlaybackState::Idle);
+    }
+
+    #[test]
+    fn prepare_then_begin_reaches_playing() {
+        let mut engine = Engine::new(100);
+        assert_eq!(engine.prepare_stream(), AckResponse::Ack);
+        assert_eq!(engine.status().playback_state, PlaybackState::Prepare);
+        assert_eq!(engine.begin(1000), AckResponse::Ack);
+        assert_eq!(engine.status().playback_state, PlaybackState::Playing);
+    }
+
+    #[test]
+    fn stop_returns_to_idle_from_any_state() {
+        let mut engine = Engine::new(100);
+        assert_eq!(engine.stop(), AckResponse::Ack);
+        assert_eq!(engine.status().playback_state, PlaybackState::Idle);
+
+        engine.prepare_stream();
+        assert_eq!(engine.stop(), AckResponse::Ack);
+        assert_eq!(engine.status().playback_state, PlaybackState::Idle);
+
+        engine.prepare_stream();
+        engine.begin(1000);
+        assert_eq!(engine.stop(), AckResponse::Ack);
+        assert_eq!(engine.status().playback_state, PlaybackState::Idle);
+    }
+
+    #[test]
+    fn write_points_reports_full_past_capacity() {
+        let mut engine = Engine::new(10);
+        assert_eq!(engine.write_points(8), AckResponse::Ack);
+        assert_eq!(engine.write_points(5), AckResponse::Full);
+        assert_eq!(engine.status().buffer_fullness, 8);
+    }
+
+    #[test]
+    fn write_points_overflow_reports_full_without_panicking() {
+        let mut engine = Engine::new(1800);
+        assert_eq!(engine.write_points(1800), AckResponse::Ack);
+        assert_eq!(engine.write_points(64000), AckResponse::Full);
+        assert_eq!(engine.status().buffer_fullness, 1800);
+    }
+
+    #[test]
+    fn drain_only_happens_while_playing() {
+        let mut engine = Engine::new(100);
+        engine.prepare_stream();
+        engine.write_points(50);
+        engine.drain(Duration::from_secs(1));
+        assert_eq!(engine.status().buffer_fullness, 50);
+
+        engine.begin(10);
+        engine.drain(Duration::from_secs(1));
+        assert_eq!(engine.status().buffer_fullness, 40);
+    }
+}
@@ -0,0 +1,147 @@
+//! Geometric and intensity transforms that may be applied to a frame of `DacPoint`s before it
+//! reaches a consumer. A `Vec<Transformer>` is configured on the emulator (see
+//! `examples/visualiser.rs`) and applied, in order, to every frame it receives.
+
+use ether_dream::protocol::DacPoint;
+
+/// A single geometric or intensity transform.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transformer {
+    /// A 3x3 affine (EDH-style homography) matrix.
+    ///
+    /// Each output coordinate is `m[0] * x + m[1] * y + m[2]` (and likewise for `y` with `m[3],
+    /// m[4], m[5]`), computed in floating point and then rounded and saturated back to the
+    /// `i16` DAC range.
+    Matrix([f32; 6]),
+    /// Translate every point by the given offset.
+    Translate { x: f32, y: f32 },
+    /// Repeat the frame `until` times, offsetting each repetition by `offset` and inserting two
+    /// blanked points between tiles (one at the end of the previous tile, one at the start of the
+    /// next) so the beam does not draw a line connecting them.
+    Replicate { until: u32, offset: (f32, f32) },
+    /// Scale `r`, `g` and `b` by a `0..=255` intensity factor.
+    Intensity(u8),
+}
+
+impl Transformer {
+    /// Apply this transform to a frame of `DacPoint`s, producing the transformed frame.
+    pub fn apply(&self, points: &[DacPoint]) -> Vec<DacPoint> {
+        match *self {
+            Transformer::Matrix(m) => points.iter().map(|p| apply_matrix(&m, p)).collect(),
+            Transformer::Translate { x, y } => {
+                let m = [1.0, 0.0, x, 0.0, 1.0, y];
+                points.iter().map(|p| apply_matrix(&m, p)).collect()
+            }
+            Transformer::Replicate { until, offset } => replicate(points, until, offset),
+            Transformer::Intensity(factor) => points.iter().map(|p| apply_intensity(factor, p)).collect(),
+        }
+    }
+}
+
+/// Apply a sequence of transforms to a frame, in order.
+pub fn apply_all(transformers: &[Transformer], points: &[DacPoint]) -> Vec<DacPoint> {
+    transformers
+        .iter()
+        .fold(points.to_vec(), |points, t| t.apply(&points))
+}
+
+fn apply_matrix(m: &[f32; 6], p: &DacPoint) -> DacPoint {
+    let x = p.x as f32;
+    let y = p.y as f32;
+    let nx = m[0] * x + m[1] * y + m[2];
+    let ny = m[3] * x + m[4] * y + m[5];
+    DacPoint {
+        x: saturate_i16(nx),
+        y: saturate_i16(ny),
+        ..*p
+    }
+}
+
+fn apply_intensity(factor: u8, p: &DacPoint) -> DacPoint {
+    let scale = factor as f32 / 255.0;
+    let scale_channel = |c: u16| (c as f32 * scale).round().min(u16::MAX as f32) as u16;
+    DacPoint {
+        r: scale_channel(p.r),
+        g: scale_channel(p.g),
+        b: scale_channel(p.b),
+        ..*p
+    }
+}
+
+fn replicate(points: &[DacPoint], until: u32, offset: (f32, f32)) -> Vec<DacPoint> {
+    let mut out = Vec::with_capacity(points.len() * until.max(1) as usize);
+    for tile in 0..until {
+        let (ox, oy) = (offset.0 * tile as f32, offset.1 * tile as f32);
+        if tile > 0 {
+            if let (Some(prev_end), Some(first)) = (out.last().cloned(), points.first()) {
+                // Consumers (e.g. the nannou example) colour a line segment by its start
+                // point, so a jump straight from the previous tile's last lit point to the
+                // next tile's first point would draw a bright line across the tile offset.
+                // Blank the previous tile's last point in place first, so that segment has
+                // zero length, then jump while already blanked.
+                out.push(DacPoint { r: 0, g: 0, b: 0, ..prev_end });
+                out.push(DacPoint {
+                    x: saturate_i16(first.x as f32 + ox),
+                    y: saturate_i16(first.y as f32 + oy),
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    ..*first
+                });
+            }
+        }
+        for p in points {
+            out.push(DacPoint {
+                x: saturate_i16(p.x as f32 + ox),
+                y: saturate_i16(p.y as f32 + oy),
+                ..*p
+            });
+        }
+    }
+    out
+}
+
+fn saturate_i16(v: f32) -> i16 {
+    v.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(x: i16, y: i16) -> DacPoint {
+        DacPoint { x, y, r: 0xffff, g: 0xffff, b: 0xffff, ..Default::default() }
+    }
+
+    fn is_blank(p: &DacPoint) -> bool {
+        p.r == 0 && p.g == 0 && p.b == 0
+    }
+
+    #[test]
+    fn replicate_inserts_no_bright_jump_between_tiles() {
+        let frame = vec![lit(0, 0), lit(100, 0)];
+        let out = Transformer::Replicate { until: 2, offset: (1000.0, 0.0) }.apply(&frame);
+
+        // The boundary between tiles is: last point of tile 0, blank-at-same-position, blank
+        // at jump target, first point of tile 1. Every segment touching the jump must start
+        // from a blanked point, since consumers colour a line by its start point.
+        let boundary = out.iter().position(|p| !is_blank(p) && p.x == 100).unwrap();
+        assert!(is_blank(&out[boundary + 1]));
+        assert!(is_blank(&out[boundary + 2]));
+    }
+
+    #[test]
+    fn matrix_saturates_to_i16_range() {
+        let p = lit(100, 100);
+        let out = Transformer::Matrix([1000.0, 0.0, 0.0, 0.0, 1000.0, 0.0]).apply(&[p]);
+        assert_eq!(out[0].x, i16::MAX);
+        assert_eq!(out[0].y, i16::MAX);
+    }
+
+    #[test]
+    fn intensity_scales_channels() {
+        let p = lit(0, 0);
+        let out = Transformer::Intensity(0).apply(&[p]);
+        assert_eq!((out[0].r, out[0].g, out[0].b), (0, 0, 0));
+    }
+}